@@ -0,0 +1,384 @@
+use crate::model::Step;
+use std::collections::VecDeque;
+
+/// One line of a hunk, tagged by how it relates to the expected transcript.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    Context(String),
+    Removed(String),
+    Added(String),
+}
+
+/// A contiguous run of diff lines, with the ranges needed for a
+/// `@@ -a,b +c,d @@` unified-diff header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    pub expected_start: usize,
+    pub expected_len: usize,
+    pub actual_start: usize,
+    pub actual_len: usize,
+    pub lines: Vec<DiffLine>,
+    /// Indices into `lines` that are immediately followed by a
+    /// "\ No newline at end of file" marker when rendered. A line lands here
+    /// when it is the true last line of its side (expected or actual) and
+    /// that side's source had no trailing newline. A hunk can carry up to
+    /// two such positions: the expected side's tail and the actual side's
+    /// tail don't have to be the same line (e.g. the actual side keeps
+    /// appending after the expected side has run out).
+    no_newline_after: Vec<usize>,
+}
+
+impl Hunk {
+    /// Render this hunk as `@@ -a,b +c,d @@` followed by its lines.
+    pub fn to_unified(&self) -> String {
+        let mut out = format!(
+            "@@ -{},{} +{},{} @@\n",
+            self.expected_start, self.expected_len, self.actual_start, self.actual_len
+        );
+        for (i, line) in self.lines.iter().enumerate() {
+            match line {
+                DiffLine::Context(l) => {
+                    out.push(' ');
+                    out.push_str(l);
+                }
+                DiffLine::Removed(l) => {
+                    out.push('-');
+                    out.push_str(l);
+                }
+                DiffLine::Added(l) => {
+                    out.push('+');
+                    out.push_str(l);
+                }
+            }
+            out.push('\n');
+            if self.no_newline_after.contains(&i) {
+                out.push_str("\\ No newline at end of file\n");
+            }
+        }
+        out
+    }
+}
+
+/// Render a full set of hunks as a unified diff.
+pub fn render_unified(hunks: &[Hunk]) -> String {
+    hunks.iter().map(Hunk::to_unified).collect()
+}
+
+/// Compare a reference solution against a student's steps and return
+/// unified-diff hunks showing where they diverge.
+///
+/// Each side is flattened to lines first (step-number markers interleaved
+/// with content, mirroring the on-disk format) so step boundaries show up
+/// in the diff like any other line. `expected_trailing_newline`/
+/// `actual_trailing_newline` come from the respective [`crate::file_io::LoadedDoc`]
+/// and control whether a "\ No newline at end of file" marker is emitted
+/// after that side's last line.
+pub fn diff_steps(
+    expected: &[Step],
+    actual: &[Step],
+    context: usize,
+    expected_trailing_newline: bool,
+    actual_trailing_newline: bool,
+) -> Vec<Hunk> {
+    let expected_lines = flatten_steps(expected);
+    let actual_lines = flatten_steps(actual);
+    diff_lines(
+        &expected_lines,
+        &actual_lines,
+        context,
+        expected_trailing_newline,
+        actual_trailing_newline,
+        |a, b| a == b,
+    )
+}
+
+/// Like [`diff_steps`], but a grading mode that ignores host-specific noise:
+/// expected lines may use `[..]`, `[CWD]`, `[HOME]`, and `[PROMPT]` markup
+/// (see [`crate::pattern`]) and still count as equal to the actual line.
+#[allow(clippy::too_many_arguments)]
+pub fn diff_steps_normalized(
+    expected: &[Step],
+    actual: &[Step],
+    context: usize,
+    expected_trailing_newline: bool,
+    actual_trailing_newline: bool,
+    cwd: &str,
+    home: &str,
+) -> Vec<Hunk> {
+    let expected_lines = flatten_steps(expected);
+    let actual_lines = flatten_steps(actual);
+    diff_lines(
+        &expected_lines,
+        &actual_lines,
+        context,
+        expected_trailing_newline,
+        actual_trailing_newline,
+        |e, a| crate::pattern::matches_pattern(e, a, cwd, home),
+    )
+}
+
+/// Flatten steps into the line sequence [`diff_lines`] compares.
+fn flatten_steps(steps: &[Step]) -> Vec<String> {
+    let mut out = Vec::new();
+    for (i, step) in steps.iter().enumerate() {
+        out.push((i + 1).to_string());
+        out.extend(step.iter().cloned());
+    }
+    out
+}
+
+enum Op {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Standard O(n*m) LCS table, backtracked into an edit script. `eq` decides
+/// whether an expected/actual line pair counts as equal, so callers can
+/// plug in pattern-aware matching instead of strict equality.
+fn lcs_ops(expected: &[String], actual: &[String], eq: impl Fn(&str, &str) -> bool) -> Vec<Op> {
+    let n = expected.len();
+    let m = actual.len();
+    let mut table = vec![vec![0u32; m + 1]; n + 1];
+
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            table[i][j] = if eq(&expected[i], &actual[j]) {
+                table[i + 1][j + 1] + 1
+            } else {
+                table[i + 1][j].max(table[i][j + 1])
+            };
+        }
+    }
+
+    let mut ops = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < n && j < m {
+        if eq(&expected[i], &actual[j]) {
+            ops.push(Op::Equal(i, j));
+            i += 1;
+            j += 1;
+        } else if table[i + 1][j] >= table[i][j + 1] {
+            ops.push(Op::Delete(i));
+            i += 1;
+        } else {
+            ops.push(Op::Insert(j));
+            j += 1;
+        }
+    }
+    while i < n {
+        ops.push(Op::Delete(i));
+        i += 1;
+    }
+    while j < m {
+        ops.push(Op::Insert(j));
+        j += 1;
+    }
+    ops
+}
+
+fn start_hunk(
+    trailing_context: &VecDeque<(usize, usize, String)>,
+    expected_idx: usize,
+    actual_idx: usize,
+) -> Hunk {
+    let (expected_start, actual_start) = trailing_context
+        .front()
+        .map(|(e, a, _)| (*e, *a))
+        .unwrap_or((expected_idx, actual_idx));
+    let lines: Vec<DiffLine> = trailing_context
+        .iter()
+        .map(|(_, _, l)| DiffLine::Context(l.clone()))
+        .collect();
+    let len = lines.len();
+    Hunk {
+        expected_start: expected_start + 1,
+        expected_len: len,
+        actual_start: actual_start + 1,
+        actual_len: len,
+        lines,
+        no_newline_after: Vec::new(),
+    }
+}
+
+/// Mark `hunk.lines[idx]` as needing a trailing "\ No newline at end of
+/// file" marker, unless it's already marked (both sides can land on the
+/// same physical line when neither has a trailing newline).
+fn mark_no_newline(hunk: &mut Hunk, idx: usize) {
+    if !hunk.no_newline_after.contains(&idx) {
+        hunk.no_newline_after.push(idx);
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn diff_lines(
+    expected: &[String],
+    actual: &[String],
+    context: usize,
+    expected_trailing_newline: bool,
+    actual_trailing_newline: bool,
+    eq: impl Fn(&str, &str) -> bool,
+) -> Vec<Hunk> {
+    let ops = lcs_ops(expected, actual, eq);
+    let last_expected_idx = expected.len().checked_sub(1);
+    let last_actual_idx = actual.len().checked_sub(1);
+
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let mut current: Option<Hunk> = None;
+    let mut trailing_context: VecDeque<(usize, usize, String)> = VecDeque::new();
+    let mut lines_since_mismatch = 0usize;
+    let (mut last_expected, mut last_actual) = (0usize, 0usize);
+
+    for op in ops {
+        match op {
+            Op::Equal(ei, ai) => {
+                let line = expected[ei].clone();
+                if let Some(hunk) = current.as_mut() {
+                    if lines_since_mismatch < context {
+                        hunk.lines.push(DiffLine::Context(line.clone()));
+                        hunk.expected_len += 1;
+                        hunk.actual_len += 1;
+                        lines_since_mismatch += 1;
+                        let idx = hunk.lines.len() - 1;
+                        if !expected_trailing_newline && Some(ei) == last_expected_idx {
+                            mark_no_newline(hunk, idx);
+                        }
+                        if !actual_trailing_newline && Some(ai) == last_actual_idx {
+                            mark_no_newline(hunk, idx);
+                        }
+                    } else {
+                        hunks.push(current.take().unwrap());
+                    }
+                }
+                if current.is_none() && context > 0 {
+                    if trailing_context.len() == context {
+                        trailing_context.pop_front();
+                    }
+                    trailing_context.push_back((ei, ai, line));
+                }
+                last_expected = ei + 1;
+                last_actual = ai + 1;
+            }
+            Op::Delete(ei) => {
+                let hunk =
+                    current.get_or_insert_with(|| start_hunk(&trailing_context, ei, last_actual));
+                hunk.lines.push(DiffLine::Removed(expected[ei].clone()));
+                hunk.expected_len += 1;
+                lines_since_mismatch = 0;
+                trailing_context.clear();
+                if !expected_trailing_newline && Some(ei) == last_expected_idx {
+                    let idx = hunk.lines.len() - 1;
+                    mark_no_newline(hunk, idx);
+                }
+                last_expected = ei + 1;
+            }
+            Op::Insert(ai) => {
+                let hunk = current
+                    .get_or_insert_with(|| start_hunk(&trailing_context, last_expected, ai));
+                hunk.lines.push(DiffLine::Added(actual[ai].clone()));
+                hunk.actual_len += 1;
+                lines_since_mismatch = 0;
+                trailing_context.clear();
+                if !actual_trailing_newline && Some(ai) == last_actual_idx {
+                    let idx = hunk.lines.len() - 1;
+                    mark_no_newline(hunk, idx);
+                }
+                last_actual = ai + 1;
+            }
+        }
+    }
+
+    if let Some(hunk) = current.take() {
+        hunks.push(hunk);
+    }
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(lines: &[&str]) -> Step {
+        lines.iter().map(|l| l.to_string()).collect()
+    }
+
+    #[test]
+    fn identical_steps_produce_no_hunks() {
+        let steps = vec![step(&["echo hi", "hi"])];
+        assert!(diff_steps(&steps, &steps, 3, true, true).is_empty());
+    }
+
+    #[test]
+    fn single_line_change_is_reported() {
+        let expected = vec![step(&["echo hi", "hi"])];
+        let actual = vec![step(&["echo hi", "bye"])];
+        let hunks = diff_steps(&expected, &actual, 3, true, true);
+        assert_eq!(hunks.len(), 1);
+        assert!(hunks[0].lines.contains(&DiffLine::Removed("hi".to_string())));
+        assert!(hunks[0].lines.contains(&DiffLine::Added("bye".to_string())));
+    }
+
+    #[test]
+    fn context_is_limited_to_requested_window() {
+        let expected = vec![step(&["a", "b", "c", "d", "e"])];
+        let actual = vec![step(&["a", "b", "X", "d", "e"])];
+        let hunks = diff_steps(&expected, &actual, 1, true, true);
+        assert_eq!(hunks.len(), 1);
+        // "1" (step marker), "a", "b" precede; only 1 line of context kept before/after
+        let contexts: Vec<_> = hunks[0]
+            .lines
+            .iter()
+            .filter(|l| matches!(l, DiffLine::Context(_)))
+            .collect();
+        assert_eq!(contexts.len(), 2); // one before, one after
+    }
+
+    #[test]
+    fn far_apart_changes_produce_separate_hunks() {
+        let expected = vec![step(&["a", "b", "c", "d", "e", "f", "g", "h", "i", "j"])];
+        let actual = vec![step(&["X", "b", "c", "d", "e", "f", "g", "h", "i", "Y"])];
+        let hunks = diff_steps(&expected, &actual, 1, true, true);
+        assert_eq!(hunks.len(), 2);
+    }
+
+    #[test]
+    fn unified_header_format() {
+        let expected = vec![step(&["a"])];
+        let actual = vec![step(&["b"])];
+        let hunks = diff_steps(&expected, &actual, 0, true, true);
+        let rendered = render_unified(&hunks);
+        assert!(rendered.starts_with("@@ -"));
+        assert!(rendered.contains("-a"));
+        assert!(rendered.contains("+b"));
+    }
+
+    #[test]
+    fn missing_trailing_newline_on_expected_is_marked() {
+        let expected = vec![step(&["a", "b"])];
+        let actual = vec![step(&["a", "X"])];
+        let hunks = diff_steps(&expected, &actual, 1, false, true);
+        let rendered = render_unified(&hunks);
+        let removed_idx = rendered.find("-b").unwrap();
+        let added_idx = rendered.find("+X").unwrap();
+        assert!(rendered[removed_idx..].contains("\\ No newline at end of file"));
+        assert!(!rendered[added_idx..].contains("\\ No newline at end of file"));
+    }
+
+    #[test]
+    fn missing_trailing_newline_on_actual_is_marked() {
+        let expected = vec![step(&["a", "b"])];
+        let actual = vec![step(&["a", "X"])];
+        let hunks = diff_steps(&expected, &actual, 1, true, false);
+        let rendered = render_unified(&hunks);
+        assert!(rendered.ends_with("\\ No newline at end of file\n"));
+    }
+
+    #[test]
+    fn trailing_newline_present_on_both_sides_emits_no_marker() {
+        let expected = vec![step(&["a", "b"])];
+        let actual = vec![step(&["a", "X"])];
+        let hunks = diff_steps(&expected, &actual, 1, true, true);
+        let rendered = render_unified(&hunks);
+        assert!(!rendered.contains("No newline"));
+    }
+}
@@ -25,6 +25,7 @@ pub struct Editor {
     pub filename: String,
     path: std::path::PathBuf,
     pub steps: Vec<Step>,
+    line_ending: crate::file_io::LineEnding,
     memo: Vec<HashMap<&'static str, usize>>,
     si: usize,
     row: usize,
@@ -37,13 +38,14 @@ pub struct Editor {
 }
 
 impl Editor {
-    pub fn new(filename: String, steps: Vec<Step>) -> Self {
+    pub fn new(filename: String, steps: Vec<Step>, line_ending: crate::file_io::LineEnding) -> Self {
         let n = steps.len();
         let path = std::path::PathBuf::from(&filename);
         Self {
             filename,
             path,
             steps,
+            line_ending,
             memo: (0..n).map(|_| HashMap::new()).collect(),
             si: 0,
             row: 0,
@@ -124,6 +126,23 @@ impl Editor {
 
     // ── drawing ──────────────────────────────────────────────
 
+    /// Map a byte offset into a `\n`-split line (see [`Step`]) to which
+    /// piece it falls on and the byte offset within that piece.
+    ///
+    /// `col` is a byte offset (as used by `String::insert`/`remove`
+    /// elsewhere in this file), so the running total consumes `part.len()`
+    /// bytes per piece plus one for the separator `\n` that `split` ate.
+    fn locate_subline(parts: &[&str], col: usize) -> (usize, usize) {
+        let mut remaining = col;
+        for (i, part) in parts.iter().enumerate() {
+            if i + 1 == parts.len() || remaining <= part.len() {
+                return (i, remaining.min(part.len()));
+            }
+            remaining -= part.len() + 1;
+        }
+        (0, 0)
+    }
+
     fn draw(&mut self, stdout: &mut io::Stdout) -> io::Result<()> {
         let (tw, th) = terminal::size()?;
         let tw = tw as usize;
@@ -172,39 +191,62 @@ impl Editor {
         let mut cursor_screen_row: u16 = 1;
         let mut cursor_screen_col: u16 = 0;
 
-        for vrow in 0..content_h {
-            let abs_row = self.scroll + vrow;
-            let screen_y = (vrow + 1) as u16; // +1 for tab bar
-            execute!(stdout, MoveTo(0, screen_y))?;
-
+        // A `Step` entry is usually one terminal row, but a backslash-folded
+        // continuation (see `file_io::StepReader`) carries internal
+        // `\n`s; writing those raw would feed real LF bytes to the terminal
+        // and desync every `MoveTo` after it. Split on `\n` and render each
+        // piece as its own row instead.
+        let mut abs_row = self.scroll;
+        let mut visual_row = 0usize;
+        while visual_row < content_h {
             if abs_row >= step.len() {
                 // empty line beyond content
+                let screen_y = (visual_row + 1) as u16; // +1 for tab bar
+                execute!(stdout, MoveTo(0, screen_y))?;
                 execute!(stdout, SetForegroundColor(Color::DarkGrey))?;
                 write!(stdout, "~")?;
                 execute!(stdout, ResetColor)?;
+                visual_row += 1;
+                abs_row += 1;
                 continue;
             }
 
             let line = &step[abs_row];
             let is_first_line = abs_row == 0;
+            let parts: Vec<&str> = line.split('\n').collect();
+            let cursor_loc = (abs_row == self.row).then(|| Self::locate_subline(&parts, self.col));
 
-            // Set color: first line green, rest white
-            if is_first_line {
-                execute!(stdout, SetForegroundColor(Color::Green))?;
-            } else {
-                execute!(stdout, SetForegroundColor(Color::White))?;
-            }
+            for (part_idx, part) in parts.iter().enumerate() {
+                if visual_row >= content_h {
+                    break;
+                }
+                let screen_y = (visual_row + 1) as u16;
+                execute!(stdout, MoveTo(0, screen_y))?;
 
-            // Render line content (no cloning, just iterate chars)
-            let display: String = line.chars().take(tw).collect();
-            write!(stdout, "{}", display)?;
-            execute!(stdout, ResetColor)?;
+                // Set color: first line green, rest white
+                if is_first_line {
+                    execute!(stdout, SetForegroundColor(Color::Green))?;
+                } else {
+                    execute!(stdout, SetForegroundColor(Color::White))?;
+                }
+
+                // Render line content (no cloning, just iterate chars)
+                let display: String = part.chars().take(tw).collect();
+                write!(stdout, "{}", display)?;
+                execute!(stdout, ResetColor)?;
+
+                // Track cursor position
+                if let Some((cursor_part, offset)) = cursor_loc {
+                    if cursor_part == part_idx {
+                        cursor_screen_row = screen_y;
+                        cursor_screen_col = offset.min(tw.saturating_sub(1)) as u16;
+                    }
+                }
 
-            // Track cursor position
-            if abs_row == self.row {
-                cursor_screen_row = screen_y;
-                cursor_screen_col = self.col.min(tw.saturating_sub(1)) as u16;
+                visual_row += 1;
             }
+
+            abs_row += 1;
         }
 
         // ── Status bar (last row) ──
@@ -471,7 +513,7 @@ impl Editor {
     // ── save ─────────────────────────────────────────────────
 
     fn save(&mut self) {
-        match crate::file_io::save_file(&self.path, &self.steps) {
+        match crate::file_io::save_file(&self.path, &self.steps, self.line_ending) {
             Ok(()) => {
                 self.dirty = false;
                 self.set_msg("Saved", MsgKind::Ok);
@@ -3,145 +3,303 @@ use std::fs;
 use std::io::{self, Write};
 use std::path::Path;
 
-/// Load a lab submission file, returning a list of steps.
+/// Line terminator detected (or chosen) for a submission file.
 ///
-/// Supports three formats:
-/// 1. **New format (no blank lines)**: Sequential step number detection.
-///    Step boundaries detected by matching the next expected step number.
-/// 2. **Old format ($ prefix)**: Lines starting with `$ ` are commands.
-///    Entries are flattened into a single Vec<String> per step.
-/// 3. **Legacy format (blank line separated)**: Step blocks with step numbers.
-///
-/// Returns a single empty step if the file is empty or cannot be read.
-pub fn load_file(path: &Path) -> Vec<Step> {
-    let content = match fs::read_to_string(path) {
-        Ok(c) => c,
-        Err(_) => return vec![new_step()],
-    };
+/// Submissions authored on Windows commonly use `\r\n`; we detect the
+/// dominant ending on load and round-trip it on save instead of silently
+/// normalizing to `\n`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineEnding {
+    Lf,
+    Crlf,
+}
 
-    if content.trim().is_empty() {
-        return vec![new_step()];
+impl LineEnding {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::Crlf => "\r\n",
+        }
     }
+}
 
-    // Detect old format: any line starts with "$ "
-    let is_old_format = content.lines().any(|l| l.starts_with("$ "));
+/// A file's parsed steps plus the line ending it was (or should be) saved
+/// with and whether the source had a trailing newline.
+pub struct LoadedDoc {
+    pub steps: Vec<Step>,
+    pub line_ending: LineEnding,
+    /// Whether the raw file ended with a newline. Feeds [`crate::diff`]'s
+    /// "\ No newline at end of file" marker; an empty file counts as `true`
+    /// (there's no dangling partial line to flag).
+    pub trailing_newline: bool,
+}
 
-    if is_old_format {
-        load_old_format(&content)
+/// Scan raw bytes and report whichever ending is more common.
+///
+/// Counts `\r\n` pairs against lone `\n`s (a `\n` not preceded by `\r`).
+/// Ties and files with no newline at all default to `Lf`.
+fn detect_line_ending(bytes: &[u8]) -> LineEnding {
+    let mut crlf = 0usize;
+    let mut lone_lf = 0usize;
+    for (i, &b) in bytes.iter().enumerate() {
+        if b == b'\n' {
+            if i > 0 && bytes[i - 1] == b'\r' {
+                crlf += 1;
+            } else {
+                lone_lf += 1;
+            }
+        }
+    }
+    if crlf > lone_lf {
+        LineEnding::Crlf
     } else {
-        load_new_format(&content)
+        LineEnding::Lf
     }
 }
 
-/// Parse the new format (no blank lines).
+/// Load a lab submission file, returning its steps and detected line ending.
 ///
-/// Sequential step number detection: track next_expected = 1, 2, 3, ...
-/// When a line equals next_expected.to_string() (trimmed), start a new step.
-/// This is robust against output containing bare numbers since we only match
-/// the NEXT expected number.
+/// Step boundaries are sequential step-number detection: a physical line
+/// equal to the next expected number (1, 2, 3, ...) starts a new step. Old
+/// and new format both fall out of the same rules (see [`StepReader`]):
+/// `$ cmd` lines store just `cmd`, bare `$` lines are dropped, and a
+/// trailing unescaped `\` folds the following physical lines into the same
+/// logical entry.
 ///
-/// Blank lines are skipped for backward compatibility with legacy format.
-fn load_new_format(content: &str) -> Vec<Step> {
-    let mut steps: Vec<Step> = Vec::new();
-    let mut current_step: Step = Vec::new();
-    let mut next_expected = 1;
+/// Returns a single empty step if the file is empty or cannot be read.
+pub fn load_file(path: &Path) -> LoadedDoc {
+    let bytes = match fs::read(path) {
+        Ok(b) => b,
+        Err(_) => {
+            return LoadedDoc {
+                steps: vec![new_step()],
+                line_ending: LineEnding::Lf,
+                trailing_newline: true,
+            }
+        }
+    };
 
-    for line in content.lines() {
-        let trimmed = line.trim();
+    let line_ending = detect_line_ending(&bytes);
+    let trailing_newline = match bytes.last() {
+        Some(&b) => b == b'\n',
+        None => true,
+    };
 
-        // Skip blank lines (for legacy format compatibility)
-        if trimmed.is_empty() {
-            continue;
-        }
+    if bytes.iter().all(u8::is_ascii_whitespace) {
+        return LoadedDoc {
+            steps: vec![new_step()],
+            line_ending,
+            trailing_newline,
+        };
+    }
 
-        // Check if this line is the next expected step number
-        if trimmed == next_expected.to_string() {
-            // Flush the current step if it has content
-            if !current_step.is_empty() {
-                steps.push(current_step.clone());
-                current_step.clear();
-            }
-            next_expected += 1;
-            continue;
+    let mut steps: Vec<Step> = Vec::new();
+    for step in parse_reader(io::Cursor::new(bytes)) {
+        match step {
+            Ok(s) => steps.push(s),
+            Err(_) => break,
         }
-
-        // Not a step number - add to current step
-        current_step.push(line.to_string());
+    }
+    if steps.is_empty() {
+        steps.push(new_step());
     }
 
-    // Flush the last step
-    if !current_step.is_empty() {
-        steps.push(current_step);
+    LoadedDoc {
+        steps,
+        line_ending,
+        trailing_newline,
     }
+}
 
-    // If we found step markers but no content, or if the file started with step 1
-    // but had no other content, handle the edge case
-    if steps.is_empty() {
-        vec![new_step()]
+/// The character joined continuation lines are glued together with when
+/// folded back into a single logical `Step` entry.
+const CONTINUATION_JOINER: &str = "\n";
+
+/// Strip an unescaped trailing backslash (a line-continuation marker).
+///
+/// A line ending in an *odd* number of backslashes is continued: the final
+/// backslash is the marker, any backslashes before it are literal escaped
+/// pairs. An even count (including zero) means no continuation.
+fn strip_unescaped_trailing_backslash(line: &str) -> Option<&str> {
+    let trailing = line.len() - line.trim_end_matches('\\').len();
+    if trailing % 2 == 1 {
+        Some(&line[..line.len() - 1])
     } else {
-        steps
+        None
     }
 }
 
-/// Parse the old `$ ` prefixed format.
-///
-/// Uses sequential step number detection (same as new format).
-/// `$ cmd` lines store just `cmd` (strip `$ `).
-/// Bare `$` lines and blank lines are skipped.
-fn load_old_format(content: &str) -> Vec<Step> {
-    let mut steps: Vec<Step> = Vec::new();
-    let mut current_step: Step = Vec::new();
-    let mut next_expected: u32 = 1;
+/// Statefully parses step-delimited transcript content one physical line at
+/// a time: sequential step-number detection, `$ ` stripping, bare `$`
+/// dropping, and backslash-continuation folding, without ever buffering
+/// more than the current step.
+pub struct StepReader<R> {
+    lines: LossyLines<R>,
+    next_expected: u32,
+    current: Step,
+    pending: Option<String>,
+    done: bool,
+}
 
-    for line in content.lines() {
-        let trimmed = line.trim();
+impl<R: io::BufRead> StepReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            lines: LossyLines::new(reader),
+            next_expected: 1,
+            current: Vec::new(),
+            pending: None,
+            done: false,
+        }
+    }
+}
+
+/// Like [`io::BufRead::lines`], but never fails on invalid UTF-8.
+///
+/// Captured program output routinely contains stray non-UTF-8 bytes (binary
+/// data written to a terminal, truncated multi-byte sequences, ...).
+/// `io::Lines` treats any such byte as a hard `InvalidData` error and stops
+/// the whole stream right there, silently discarding every step after it.
+/// This reads raw bytes per line instead and lossily decodes them (invalid
+/// bytes become `U+FFFD`), so a single bad byte in one line of output can't
+/// erase the rest of the transcript.
+struct LossyLines<R> {
+    reader: R,
+    buf: Vec<u8>,
+}
 
-        // Skip blank lines entirely
-        if trimmed.is_empty() {
-            continue;
+impl<R: io::BufRead> LossyLines<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
         }
+    }
+}
 
-        // Check if this is the next expected step number
-        if trimmed == next_expected.to_string() {
-            if !current_step.is_empty() {
-                steps.push(current_step.clone());
-                current_step.clear();
+impl<R: io::BufRead> Iterator for LossyLines<R> {
+    type Item = io::Result<String>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.buf.clear();
+        match self.reader.read_until(b'\n', &mut self.buf) {
+            Ok(0) => None,
+            Ok(_) => {
+                if self.buf.last() == Some(&b'\n') {
+                    self.buf.pop();
+                    if self.buf.last() == Some(&b'\r') {
+                        self.buf.pop();
+                    }
+                }
+                Some(Ok(String::from_utf8_lossy(&self.buf).into_owned()))
             }
-            next_expected += 1;
-            continue;
+            Err(e) => Some(Err(e)),
         }
+    }
+}
 
-        // Bare "$" - drop it
-        if line == "$" {
-            continue;
-        }
+impl<R: io::BufRead> Iterator for StepReader<R> {
+    type Item = io::Result<Step>;
 
-        // "$ cmd" - store just the command (strip "$ ")
-        if line.starts_with("$ ") {
-            current_step.push(line[2..].to_string());
-            continue;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
         }
+        loop {
+            let raw = match self.lines.next() {
+                Some(Ok(l)) => l,
+                Some(Err(e)) => {
+                    self.done = true;
+                    return Some(Err(e));
+                }
+                None => {
+                    self.done = true;
+                    if let Some(pending) = self.pending.take() {
+                        self.current.push(pending);
+                    }
+                    return if self.current.is_empty() {
+                        None
+                    } else {
+                        Some(Ok(std::mem::take(&mut self.current)))
+                    };
+                }
+            };
+
+            // Mid-continuation: this physical line is not checked against
+            // the `$ `-stripping rule, but a bare step number still closes
+            // the step out from under the continuation instead of being
+            // swallowed into it.
+            if let Some(mut buf) = self.pending.take() {
+                if raw.trim() == self.next_expected.to_string() {
+                    self.next_expected += 1;
+                    self.current.push(buf);
+                    return Some(Ok(std::mem::take(&mut self.current)));
+                }
+                match strip_unescaped_trailing_backslash(&raw) {
+                    Some(stripped) => {
+                        buf.push_str(CONTINUATION_JOINER);
+                        buf.push_str(stripped);
+                        self.pending = Some(buf);
+                    }
+                    None => {
+                        buf.push_str(CONTINUATION_JOINER);
+                        buf.push_str(&raw);
+                        self.current.push(buf);
+                    }
+                }
+                continue;
+            }
 
-        // Regular output line
-        current_step.push(line.to_string());
-    }
+            let trimmed = raw.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
 
-    // Flush remaining
-    if !current_step.is_empty() {
-        steps.push(current_step);
-    }
+            if trimmed == self.next_expected.to_string() {
+                self.next_expected += 1;
+                if !self.current.is_empty() {
+                    return Some(Ok(std::mem::take(&mut self.current)));
+                }
+                continue;
+            }
 
-    if steps.is_empty() {
-        vec![new_step()]
-    } else {
-        steps
+            if raw == "$" {
+                continue;
+            }
+
+            let content = if let Some(rest) = raw.strip_prefix("$ ") {
+                rest.to_string()
+            } else {
+                raw
+            };
+
+            match strip_unescaped_trailing_backslash(&content) {
+                Some(stripped) => self.pending = Some(stripped.to_string()),
+                None => self.current.push(content),
+            }
+        }
     }
 }
 
+/// Parse step-delimited transcript content from any buffered reader,
+/// yielding each [`Step`] as soon as its terminating step number is seen.
+///
+/// Unlike [`load_file`], this never materializes the whole input: it's
+/// usable from stdin, a network pipe, or anything else that only needs to
+/// scan or re-emit steps (a diff or validation pass) in constant memory.
+pub fn parse_reader<R: io::BufRead>(reader: R) -> impl Iterator<Item = io::Result<Step>> {
+    StepReader::new(reader)
+}
+
 /// Save steps to a lab submission file in the clean format.
 ///
 /// Format: ZERO blank lines. Step number on its own line, then content lines,
-/// then next step number. File ends with exactly one newline.
+/// then next step number. File ends with exactly one newline. `line_ending`
+/// controls the terminator emitted after the step number and every content
+/// line, so a submission's original CRLF/LF convention survives a save.
+///
+/// A content line folded from a backslash-continued command (see
+/// [`StepReader`]) carries an internal `\n`; it is re-split into
+/// physical lines joined by a trailing `\` so the continuation round-trips.
 ///
 /// Example:
 /// ```
@@ -152,27 +310,40 @@ fn load_old_format(content: &str) -> Vec<Step> {
 /// {giant:~} ls
 /// file.txt
 /// ```
-pub fn save_file(path: &Path, steps: &[Step]) -> io::Result<()> {
+pub fn save_file(path: &Path, steps: &[Step], line_ending: LineEnding) -> io::Result<()> {
+    let eol = line_ending.as_str();
     let mut output = String::new();
 
     for (i, step) in steps.iter().enumerate() {
         // Step number (1-indexed)
         output.push_str(&(i + 1).to_string());
-        output.push('\n');
+        output.push_str(eol);
 
         // All lines of the step
         for line in step {
-            output.push_str(line);
-            output.push('\n');
+            if line.contains(CONTINUATION_JOINER) {
+                let parts: Vec<&str> = line.split(CONTINUATION_JOINER).collect();
+                let last = parts.len() - 1;
+                for (j, part) in parts.iter().enumerate() {
+                    output.push_str(part);
+                    if j < last {
+                        output.push('\\');
+                    }
+                    output.push_str(eol);
+                }
+            } else {
+                output.push_str(line);
+                output.push_str(eol);
+            }
         }
     }
 
     // Strip trailing blank lines, then ensure exactly one trailing newline
-    let trimmed = output.trim_end_matches('\n');
+    let trimmed = output.trim_end_matches(eol);
     let final_output = if trimmed.is_empty() {
-        String::from("\n")
+        eol.to_string()
     } else {
-        format!("{}\n", trimmed)
+        format!("{}{}", trimmed, eol)
     };
 
     let mut file = fs::File::create(path)?;
@@ -197,61 +368,92 @@ mod tests {
     #[test]
     fn load_empty_file() {
         let f = tmp_file("");
-        let steps = load_file(f.path());
-        assert_eq!(steps.len(), 1);
-        assert_eq!(steps[0], vec![String::new()]);
+        let doc = load_file(f.path());
+        assert_eq!(doc.steps.len(), 1);
+        assert_eq!(doc.steps[0], vec![String::new()]);
+        assert_eq!(doc.line_ending, LineEnding::Lf);
     }
 
     #[test]
     fn load_nonexistent_file() {
-        let steps = load_file(Path::new("/tmp/does_not_exist_lab_editor_test.txt"));
-        assert_eq!(steps.len(), 1);
+        let doc = load_file(Path::new("/tmp/does_not_exist_lab_editor_test.txt"));
+        assert_eq!(doc.steps.len(), 1);
+    }
+
+    #[test]
+    fn detects_missing_trailing_newline() {
+        let f = tmp_file("1\necho hi\nhi");
+        let doc = load_file(f.path());
+        assert!(!doc.trailing_newline);
+    }
+
+    #[test]
+    fn detects_present_trailing_newline() {
+        let f = tmp_file("1\necho hi\nhi\n");
+        let doc = load_file(f.path());
+        assert!(doc.trailing_newline);
     }
 
     #[test]
     fn load_new_format_single_step() {
         let f = tmp_file("1\necho hello\nhello\n");
-        let steps = load_file(f.path());
-        assert_eq!(steps.len(), 1);
-        assert_eq!(steps[0], vec!["echo hello", "hello"]);
+        let doc = load_file(f.path());
+        assert_eq!(doc.steps.len(), 1);
+        assert_eq!(doc.steps[0], vec!["echo hello", "hello"]);
     }
 
     #[test]
     fn load_new_format_multiple_steps() {
         let f = tmp_file("1\nfirst line\n2\nsecond line\nmore output\n");
-        let steps = load_file(f.path());
-        assert_eq!(steps.len(), 2);
-        assert_eq!(steps[0], vec!["first line"]);
-        assert_eq!(steps[1], vec!["second line", "more output"]);
+        let doc = load_file(f.path());
+        assert_eq!(doc.steps.len(), 2);
+        assert_eq!(doc.steps[0], vec!["first line"]);
+        assert_eq!(doc.steps[1], vec!["second line", "more output"]);
     }
 
     #[test]
     fn load_new_format_with_number_in_output() {
         // First "2" is the step delimiter, second "2" is output in step 2
         let f = tmp_file("1\necho 2\n2\n2\nls\nfile.txt\n");
-        let steps = load_file(f.path());
-        assert_eq!(steps.len(), 2);
-        assert_eq!(steps[0], vec!["echo 2"]);
-        assert_eq!(steps[1], vec!["2", "ls", "file.txt"]);
+        let doc = load_file(f.path());
+        assert_eq!(doc.steps.len(), 2);
+        assert_eq!(doc.steps[0], vec!["echo 2"]);
+        assert_eq!(doc.steps[1], vec!["2", "ls", "file.txt"]);
+    }
+
+    #[test]
+    fn load_new_format_tolerates_invalid_utf8_in_output() {
+        // A stray non-UTF-8 byte in captured output must not truncate the
+        // rest of the transcript: it should be lossily decoded, not treated
+        // as a parse error that aborts the whole file.
+        let mut f = tempfile::NamedTempFile::new().unwrap();
+        f.write_all(b"1\nfirst \xff line\n2\nsecond line\n").unwrap();
+        f.flush().unwrap();
+
+        let doc = load_file(f.path());
+        assert_eq!(doc.steps.len(), 2);
+        assert!(doc.steps[0][0].contains("first"));
+        assert!(doc.steps[0][0].contains("line"));
+        assert_eq!(doc.steps[1], vec!["second line"]);
     }
 
     #[test]
     fn load_old_format_with_dollar_prefix() {
         let f = tmp_file("1\n$ echo hello\nhello\n$\n$ ls\nfile.txt\n");
-        let steps = load_file(f.path());
-        assert_eq!(steps.len(), 1);
+        let doc = load_file(f.path());
+        assert_eq!(doc.steps.len(), 1);
         // Flattened: all lines in one Vec<String>
-        assert_eq!(steps[0], vec!["echo hello", "hello", "ls", "file.txt"]);
+        assert_eq!(doc.steps[0], vec!["echo hello", "hello", "ls", "file.txt"]);
     }
 
     #[test]
     fn load_old_format_multi_step() {
         let content = "1\n$ cmd1\nout1\n$\n\n2\n$ cmd2\nout2\n";
         let f = tmp_file(content);
-        let steps = load_file(f.path());
-        assert_eq!(steps.len(), 2);
-        assert_eq!(steps[0], vec!["cmd1", "out1"]);
-        assert_eq!(steps[1], vec!["cmd2", "out2"]);
+        let doc = load_file(f.path());
+        assert_eq!(doc.steps.len(), 2);
+        assert_eq!(doc.steps[0], vec!["cmd1", "out1"]);
+        assert_eq!(doc.steps[1], vec!["cmd2", "out2"]);
     }
 
     #[test]
@@ -261,12 +463,12 @@ mod tests {
             vec!["ls".to_string(), "file.txt".to_string()],
         ];
         let f = tempfile::NamedTempFile::new().unwrap();
-        save_file(f.path(), &steps).unwrap();
+        save_file(f.path(), &steps, LineEnding::Lf).unwrap();
 
-        let loaded = load_file(f.path());
-        assert_eq!(loaded.len(), 2);
-        assert_eq!(loaded[0], vec!["echo hello", "hello"]);
-        assert_eq!(loaded[1], vec!["ls", "file.txt"]);
+        let doc = load_file(f.path());
+        assert_eq!(doc.steps.len(), 2);
+        assert_eq!(doc.steps[0], vec!["echo hello", "hello"]);
+        assert_eq!(doc.steps[1], vec!["ls", "file.txt"]);
     }
 
     #[test]
@@ -276,7 +478,7 @@ mod tests {
             vec!["pwd".to_string(), "/home".to_string()],
         ];
         let f = tempfile::NamedTempFile::new().unwrap();
-        save_file(f.path(), &steps).unwrap();
+        save_file(f.path(), &steps, LineEnding::Lf).unwrap();
 
         let content = fs::read_to_string(f.path()).unwrap();
         // NO blank lines between steps!
@@ -288,7 +490,7 @@ mod tests {
         // Empty step: just the step number
         let steps = vec![vec![], vec!["cmd".to_string()]];
         let f = tempfile::NamedTempFile::new().unwrap();
-        save_file(f.path(), &steps).unwrap();
+        save_file(f.path(), &steps, LineEnding::Lf).unwrap();
 
         let content = fs::read_to_string(f.path()).unwrap();
         assert_eq!(content, "1\n2\ncmd\n");
@@ -298,7 +500,7 @@ mod tests {
     fn save_empty_steps() {
         let steps: Vec<Step> = vec![];
         let f = tempfile::NamedTempFile::new().unwrap();
-        save_file(f.path(), &steps).unwrap();
+        save_file(f.path(), &steps, LineEnding::Lf).unwrap();
 
         let content = fs::read_to_string(f.path()).unwrap();
         assert_eq!(content, "\n");
@@ -308,11 +510,11 @@ mod tests {
     fn load_legacy_blank_line_format() {
         // Legacy format with blank lines between steps
         let f = tmp_file("1\nfirst line\n\n2\nsecond line\n");
-        let steps = load_file(f.path());
+        let doc = load_file(f.path());
         // Should handle gracefully - blank lines are skipped
-        assert_eq!(steps.len(), 2);
-        assert_eq!(steps[0], vec!["first line"]);
-        assert_eq!(steps[1], vec!["second line"]);
+        assert_eq!(doc.steps.len(), 2);
+        assert_eq!(doc.steps[0], vec!["first line"]);
+        assert_eq!(doc.steps[1], vec!["second line"]);
     }
 
     #[test]
@@ -333,9 +535,130 @@ $ man cat\n\
 CAT(1)     General Commands Manual     CAT(1)\n\
 $\n";
         let f = tmp_file(content);
-        let steps = load_file(f.path());
+        let doc = load_file(f.path());
+        assert_eq!(doc.steps.len(), 2);
+        assert_eq!(doc.steps[0][0], "man man");
+        assert_eq!(doc.steps[1][0], "man cat");
+    }
+
+    #[test]
+    fn load_detects_crlf_majority() {
+        let f = tmp_file("1\r\necho hi\r\nhi\r\n");
+        let doc = load_file(f.path());
+        assert_eq!(doc.line_ending, LineEnding::Crlf);
+        assert_eq!(doc.steps[0], vec!["echo hi", "hi"]);
+    }
+
+    #[test]
+    fn load_detects_lf_when_no_crlf() {
+        let f = tmp_file("1\necho hi\nhi\n");
+        let doc = load_file(f.path());
+        assert_eq!(doc.line_ending, LineEnding::Lf);
+    }
+
+    #[test]
+    fn save_emits_crlf_when_requested() {
+        let steps = vec![vec!["echo hi".to_string(), "hi".to_string()]];
+        let f = tempfile::NamedTempFile::new().unwrap();
+        save_file(f.path(), &steps, LineEnding::Crlf).unwrap();
+
+        let content = fs::read_to_string(f.path()).unwrap();
+        assert_eq!(content, "1\r\necho hi\r\nhi\r\n");
+    }
+
+    #[test]
+    fn new_format_joins_backslash_continuation() {
+        let f = tmp_file("1\necho one \\\ntwo \\\nthree\nout\n");
+        let doc = load_file(f.path());
+        assert_eq!(doc.steps.len(), 1);
+        assert_eq!(doc.steps[0], vec!["echo one \ntwo \nthree", "out"]);
+    }
+
+    #[test]
+    fn old_format_joins_backslash_continuation() {
+        let f = tmp_file("1\n$ echo one \\\ntwo\nout\n$\n");
+        let doc = load_file(f.path());
+        assert_eq!(doc.steps.len(), 1);
+        assert_eq!(doc.steps[0], vec!["echo one \ntwo", "out"]);
+    }
+
+    #[test]
+    fn step_number_after_continuation_is_not_swallowed() {
+        let f = tmp_file("1\necho one \\\ntwo\n2\nsecond step\n");
+        let doc = load_file(f.path());
+        assert_eq!(doc.steps.len(), 2);
+        assert_eq!(doc.steps[0], vec!["echo one \ntwo"]);
+        assert_eq!(doc.steps[1], vec!["second step"]);
+    }
+
+    #[test]
+    fn step_number_landing_on_continuation_line_closes_step() {
+        // The continuation line itself is a bare "2": it must close step 1
+        // rather than being folded in, and step numbering must keep
+        // advancing correctly afterward.
+        let f = tmp_file("1\necho hi \\\n2\nls\nout2\n3\npwd\nout3\n");
+        let doc = load_file(f.path());
+        assert_eq!(doc.steps.len(), 3);
+        assert_eq!(doc.steps[0], vec!["echo hi "]);
+        assert_eq!(doc.steps[1], vec!["ls", "out2"]);
+        assert_eq!(doc.steps[2], vec!["pwd", "out3"]);
+    }
+
+    #[test]
+    fn escaped_trailing_backslash_is_not_a_continuation() {
+        // Two trailing backslashes: the first is a literal backslash, not
+        // a continuation marker, so the line is not joined.
+        let f = tmp_file("1\npath\\\\\nnext\n");
+        let doc = load_file(f.path());
+        assert_eq!(doc.steps[0], vec!["path\\\\", "next"]);
+    }
+
+    #[test]
+    fn save_resplits_folded_continuation() {
+        let steps = vec![vec!["echo one\ntwo".to_string()]];
+        let f = tempfile::NamedTempFile::new().unwrap();
+        save_file(f.path(), &steps, LineEnding::Lf).unwrap();
+
+        let content = fs::read_to_string(f.path()).unwrap();
+        assert_eq!(content, "1\necho one\\\ntwo\n");
+    }
+
+    #[test]
+    fn continuation_roundtrips_through_save_and_load() {
+        let f = tmp_file("1\necho one \\\ntwo\nout\n");
+        let doc = load_file(f.path());
+
+        let f2 = tempfile::NamedTempFile::new().unwrap();
+        save_file(f2.path(), &doc.steps, doc.line_ending).unwrap();
+        let reloaded = load_file(f2.path());
+
+        assert_eq!(reloaded.steps, doc.steps);
+    }
+
+    #[test]
+    fn parse_reader_yields_steps_lazily_from_any_bufread() {
+        let content = "1\nfirst\n2\nsecond\nmore\n";
+        let reader = io::Cursor::new(content.as_bytes());
+
+        let steps: Vec<Step> = parse_reader(reader)
+            .collect::<io::Result<Vec<Step>>>()
+            .unwrap();
+
         assert_eq!(steps.len(), 2);
-        assert_eq!(steps[0][0], "man man");
-        assert_eq!(steps[1][0], "man cat");
+        assert_eq!(steps[0], vec!["first"]);
+        assert_eq!(steps[1], vec!["second", "more"]);
+    }
+
+    #[test]
+    fn parse_reader_matches_load_file_on_old_format_with_continuation() {
+        let content = "1\n$ echo one \\\ntwo\nout\n$\n2\nsecond\n";
+        let f = tmp_file(content);
+        let doc = load_file(f.path());
+
+        let steps: Vec<Step> = parse_reader(io::Cursor::new(content.as_bytes()))
+            .collect::<io::Result<Vec<Step>>>()
+            .unwrap();
+
+        assert_eq!(steps, doc.steps);
     }
 }
@@ -1,6 +1,8 @@
+mod diff;
 mod editor;
 mod file_io;
 mod model;
+mod pattern;
 
 use std::io::{self, Write};
 use std::path::Path;
@@ -14,6 +16,11 @@ fn main() {
         return;
     }
 
+    if args[0] == "diff" {
+        run_diff(&args[1..]);
+        return;
+    }
+
     // Check for help flag
     if args.iter().any(|a| a == "-h" || a == "--help") {
         print_usage();
@@ -83,6 +90,7 @@ fn print_usage() {
     println!(
         "\
 Usage: lab-editor [OPTIONS] [FILE]
+       lab-editor diff [-CN] [--normalize] <REFERENCE> <ACTUAL>
 
 Open or create a lab submission file for editing.
 
@@ -100,10 +108,73 @@ Examples:
   lab-editor myfile.txt           Open/create myfile.txt with 6 steps
   lab-editor myfile.txt -s8       Open/create myfile.txt with 8 steps
   lab-editor -a1 -c2 -l1 -s6     Creates activity-01_ch_02_lab_01.txt (6 steps)
+  lab-editor diff ref.txt sub.txt Show a unified diff between two submissions
   lab-editor                      Interactive setup"
     );
 }
 
+/// `lab-editor diff [-CN] [--normalize] <REFERENCE> <ACTUAL>` — print a
+/// unified diff between a reference solution and a student's submission.
+///
+/// `--normalize` ignores host-specific noise (prompts, `$HOME`/cwd paths)
+/// that the reference marks up with `[..]`/`[CWD]`/`[HOME]`/`[PROMPT]`.
+fn run_diff(args: &[String]) {
+    let mut context: usize = 3;
+    let mut normalize = false;
+    let mut paths: Vec<&String> = Vec::new();
+
+    for arg in args {
+        if let Some(rest) = arg.strip_prefix("-C") {
+            context = parse_flag_value(rest, "-C") as usize;
+        } else if arg == "--normalize" {
+            normalize = true;
+        } else {
+            paths.push(arg);
+        }
+    }
+
+    let (expected_path, actual_path) = match (paths.first(), paths.get(1)) {
+        (Some(e), Some(a)) => (e.as_str(), a.as_str()),
+        _ => {
+            eprintln!("Usage: lab-editor diff [-CN] [--normalize] <REFERENCE> <ACTUAL>");
+            std::process::exit(1);
+        }
+    };
+
+    let expected_doc = file_io::load_file(Path::new(expected_path));
+    let actual_doc = file_io::load_file(Path::new(actual_path));
+
+    let hunks = if normalize {
+        let cwd = std::env::current_dir()
+            .map(|p| p.display().to_string())
+            .unwrap_or_default();
+        let home = std::env::var("HOME").unwrap_or_default();
+        diff::diff_steps_normalized(
+            &expected_doc.steps,
+            &actual_doc.steps,
+            context,
+            expected_doc.trailing_newline,
+            actual_doc.trailing_newline,
+            &cwd,
+            &home,
+        )
+    } else {
+        diff::diff_steps(
+            &expected_doc.steps,
+            &actual_doc.steps,
+            context,
+            expected_doc.trailing_newline,
+            actual_doc.trailing_newline,
+        )
+    };
+
+    if hunks.is_empty() {
+        println!("No differences.");
+    } else {
+        print!("{}", diff::render_unified(&hunks));
+    }
+}
+
 fn interactive_setup() {
     println!("=== Lab Editor Setup ===");
     println!();
@@ -152,10 +223,11 @@ fn prompt_required(label: &str) -> u32 {
 fn run_editor(filename: &str, num_steps: usize) {
     let path = Path::new(filename);
 
-    let mut steps = if path.exists() {
-        file_io::load_file(path)
+    let (mut steps, line_ending) = if path.exists() {
+        let doc = file_io::load_file(path);
+        (doc.steps, doc.line_ending)
     } else {
-        model::make_steps(num_steps)
+        (model::make_steps(num_steps), file_io::LineEnding::Lf)
     };
 
     // Pad to requested step count if file had fewer
@@ -163,7 +235,7 @@ fn run_editor(filename: &str, num_steps: usize) {
         steps.push(model::new_step());
     }
 
-    let mut ed = editor::Editor::new(filename.to_string(), steps);
+    let mut ed = editor::Editor::new(filename.to_string(), steps, line_ending);
 
     if let Err(e) = ed.run() {
         eprintln!("Editor error: {}", e);
@@ -1,4 +1,10 @@
 /// A step: flat list of lines. No round/entry nesting.
+///
+/// An element is usually one on-disk/on-screen line, but a backslash-folded
+/// continuation (see `file_io::StepReader`) carries an internal
+/// `\n` joining what were several physical lines into one logical entry.
+/// Consumers that render a `Step` element as a single terminal row (see
+/// `editor::Editor::draw`) must split on `\n` first.
 pub type Step = Vec<String>;
 
 pub fn new_step() -> Step {
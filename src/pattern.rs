@@ -0,0 +1,213 @@
+//! Wildcard/normalization matching for comparing volatile shell transcripts.
+//!
+//! Expected lines can carry inline markup that tolerates nondeterministic
+//! output: a literal prompt like `{giant:~}` or a timestamp would otherwise
+//! break a plain string comparison.
+
+/// A segment of an expected line after splitting out markup tokens.
+enum Segment {
+    Literal(String),
+    /// `[..]` — zero or more characters, non-greedy.
+    Wildcard,
+    /// `[PROMPT]` — a `{host:path}` style prompt prefix.
+    Prompt,
+}
+
+/// Expand `[CWD]`/`[HOME]` in an expected line to the caller-supplied paths.
+///
+/// This is a plain substitution (not a wildcard): the expected line must
+/// contain the literal path at that position once expanded.
+pub fn normalize_line(expected_line: &str, cwd: &str, home: &str) -> String {
+    expected_line.replace("[CWD]", cwd).replace("[HOME]", home)
+}
+
+/// Does `actual_line` satisfy the pattern described by `expected_line`?
+///
+/// Supports inline markup in `expected_line`:
+/// - `[..]` matches zero-or-more characters on the same line.
+/// - `[CWD]` / `[HOME]` expand to `cwd` / `home` before matching.
+/// - `[PROMPT]` matches any `{host:path}` style prompt prefix.
+pub fn matches_pattern(expected_line: &str, actual_line: &str, cwd: &str, home: &str) -> bool {
+    let expanded = normalize_line(expected_line, cwd, home);
+    let segments = parse_segments(&expanded);
+    match_segments(&segments, actual_line)
+}
+
+fn parse_segments(line: &str) -> Vec<Segment> {
+    const WILDCARD: &str = "[..]";
+    const PROMPT: &str = "[PROMPT]";
+
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut rest = line;
+
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix(WILDCARD) {
+            segments.push(Segment::Literal(std::mem::take(&mut literal)));
+            segments.push(Segment::Wildcard);
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix(PROMPT) {
+            segments.push(Segment::Literal(std::mem::take(&mut literal)));
+            segments.push(Segment::Prompt);
+            rest = after;
+        } else {
+            let mut chars = rest.chars();
+            let c = chars.next().unwrap();
+            literal.push(c);
+            rest = chars.as_str();
+        }
+    }
+    // Drop a trailing empty literal so a `[..]`/`[PROMPT]` at the end of the
+    // line is recognized as the last segment, not followed by an empty one.
+    if !literal.is_empty() || segments.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+    segments
+}
+
+/// Anchor each literal segment left-to-right in `actual`. A literal that
+/// follows `[..]` is found at its first occurrence after the previous match;
+/// any other literal must start right at it. A trailing `[..]` matches
+/// whatever remains; a trailing literal or `[PROMPT]` must reach the end of
+/// the line.
+fn match_segments(segments: &[Segment], actual: &str) -> bool {
+    let mut pos = 0usize;
+    let mut after_wildcard = false;
+
+    for (i, segment) in segments.iter().enumerate() {
+        let is_last = i + 1 == segments.len();
+        match segment {
+            Segment::Literal(lit) => {
+                if lit.is_empty() {
+                    continue;
+                }
+                if !after_wildcard {
+                    if !actual[pos..].starts_with(lit.as_str()) {
+                        return false;
+                    }
+                    pos += lit.len();
+                } else if is_last {
+                    // Must reach the end of the line, so anchor it there.
+                    if !actual[pos..].ends_with(lit.as_str()) {
+                        return false;
+                    }
+                    pos = actual.len();
+                } else {
+                    match actual[pos..].find(lit.as_str()) {
+                        Some(offset) => pos += offset + lit.len(),
+                        None => return false,
+                    }
+                }
+                after_wildcard = false;
+            }
+            Segment::Wildcard => {
+                if is_last {
+                    return true;
+                }
+                after_wildcard = true;
+            }
+            Segment::Prompt => {
+                let hay = &actual[pos..];
+                // Anchored at `pos` (mirrors the `Literal` arm above) unless
+                // a preceding `[..]` lets the prompt be found anywhere from
+                // `pos` onward.
+                let found = if !after_wildcard {
+                    find_prompt(hay).map(|len| (0, len))
+                } else {
+                    hay.char_indices()
+                        .find_map(|(start, _)| find_prompt(&hay[start..]).map(|len| (start, len)))
+                };
+                match found {
+                    Some((start, len)) => pos += start + len,
+                    None => return false,
+                }
+                after_wildcard = false;
+            }
+        }
+    }
+
+    pos == actual.len()
+}
+
+/// Find the shortest `{host:path}`-shaped prefix at the start of `s`,
+/// returning the byte length consumed.
+fn find_prompt(s: &str) -> Option<usize> {
+    let mut chars = s.char_indices();
+    match chars.next() {
+        Some((_, '{')) => {}
+        _ => return None,
+    }
+    let colon = chars.find(|&(_, c)| c == ':' || c == '}')?;
+    if colon.1 != ':' {
+        return None;
+    }
+    let (close_idx, close_ch) = chars.find(|&(_, c)| c == '}' || c == '{')?;
+    if close_ch != '}' {
+        return None;
+    }
+    Some(close_idx + close_ch.len_utf8())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exact_match_with_no_markup() {
+        assert!(matches_pattern("hello", "hello", "", ""));
+        assert!(!matches_pattern("hello", "goodbye", "", ""));
+    }
+
+    #[test]
+    fn wildcard_matches_middle() {
+        assert!(matches_pattern("foo[..]bar", "foo123bar", "", ""));
+        assert!(!matches_pattern("foo[..]bar", "foobaz", "", ""));
+    }
+
+    #[test]
+    fn wildcard_at_end_matches_remainder() {
+        assert!(matches_pattern("ls -la[..]", "ls -la /home/user", "", ""));
+    }
+
+    #[test]
+    fn cwd_and_home_expand_to_supplied_paths() {
+        assert!(matches_pattern(
+            "cd [HOME]/projects",
+            "cd /home/alice/projects",
+            "/home/alice",
+            "/home/alice"
+        ));
+        assert!(matches_pattern(
+            "pwd: [CWD]",
+            "pwd: /tmp/lab",
+            "/tmp/lab",
+            "/home/alice"
+        ));
+    }
+
+    #[test]
+    fn prompt_token_matches_host_path_prefix() {
+        assert!(matches_pattern("[PROMPT] ls", "{giant:~} ls", "", ""));
+        assert!(matches_pattern(
+            "[PROMPT] echo hi",
+            "{box:/tmp/lab} echo hi",
+            "",
+            ""
+        ));
+        assert!(!matches_pattern("[PROMPT] ls", "ls", "", ""));
+    }
+
+    #[test]
+    fn wildcard_before_prompt_skips_leading_noise() {
+        assert!(matches_pattern("[..][PROMPT] ls", "noise {giant:~} ls", "", ""));
+        assert!(!matches_pattern("[..][PROMPT] ls", "noise ls", "", ""));
+    }
+
+    #[test]
+    fn normalize_line_only_substitutes_paths() {
+        assert_eq!(
+            normalize_line("cd [CWD] && ls [HOME]", "/a", "/b"),
+            "cd /a && ls /b"
+        );
+    }
+}